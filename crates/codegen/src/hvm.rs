@@ -0,0 +1,146 @@
+use hir::{IRKind, Value, IR};
+
+use crate::Codegen;
+
+/// Lower the same `Vec<IR>` the TypeScript backend consumes to HVM
+/// (Higher-order Virtual Machine) rewrite rules, so hazure programs can run
+/// on a parallel interpreter instead of Deno.
+impl Codegen {
+    pub(crate) fn gen_hvm(&mut self, irs: Vec<IR>) {
+        self.emit(format!("// Auto-generated by hazure compiler version {}\n", env!("CARGO_PKG_VERSION")));
+
+        // `Case` can't lower to rule definitions in expression position, so
+        // it hoists its rules into `aux` and every top-level IR is rendered
+        // into `main` first; `aux` is only safe to emit once that's done.
+        let mut aux = Vec::new();
+        let mut counter = 0usize;
+        let mut main = Vec::new();
+        for ir in irs {
+            main.push(self.gen_ir_hvm(&ir.kind, &mut aux, &mut counter));
+        }
+
+        for rule in main {
+            self.emit(&rule);
+            self.emit("\n");
+        }
+        for rule in aux {
+            self.emit(&rule);
+            self.emit("\n");
+        }
+
+        self.emit("(Main) = (F_main)\n");
+    }
+
+    fn gen_ir_hvm(&self, ir: &IRKind, aux: &mut Vec<String>, counter: &mut usize) -> String {
+        match ir {
+            IRKind::Define { name, value, .. } => {
+                format!("(F_{} ) = {}", name, self.gen_ir_hvm(value, aux, counter))
+            }
+
+            IRKind::Call { name, args } => {
+                format!(
+                    "(F_{}{})",
+                    name,
+                    args.iter()
+                        .map(|arg| format!(" {}", self.gen_ir_hvm(arg, aux, counter)))
+                        .collect::<String>(),
+                )
+            }
+
+            IRKind::Intrinsic { name, args } => {
+                format!(
+                    "(IO.{}{})",
+                    name,
+                    args.iter()
+                        .map(|arg| format!(" {}", self.gen_ir_hvm(arg, aux, counter)))
+                        .collect::<String>(),
+                )
+            }
+
+            IRKind::Fun { name, args, body, .. } => {
+                let params = args
+                    .iter()
+                    .map(|(arg_name, _)| format!(" v_{}", arg_name))
+                    .collect::<String>();
+                format!("(F_{}{}) = {}", name, params, self.gen_ir_hvm(body, aux, counter))
+            }
+
+            IRKind::Return { value } => self.gen_ir_hvm(value, aux, counter),
+
+            IRKind::Do { body } => body
+                .iter()
+                .map(|stmt| self.gen_ir_hvm(&stmt.kind, aux, counter))
+                .collect::<Vec<_>>()
+                .join("\n"),
+
+            IRKind::If { cond, body, else_body } => format!(
+                "(If {} {} {})",
+                self.gen_ir_hvm(cond, aux, counter),
+                self.gen_ir_hvm(body, aux, counter),
+                self.gen_ir_hvm(else_body, aux, counter),
+            ),
+
+            // `Case` can't become rule definitions where an expression is
+            // expected, so it hoists a fresh, uniquely-named rule set into
+            // `aux` (one rule per case, plus a catch-all for `default`) and
+            // the call site just applies that synthesized function to `cond`.
+            IRKind::Case { cond, cases, default } => {
+                *counter += 1;
+                let match_fn = format!("Match{}", counter);
+                let cond_str = self.gen_ir_hvm(cond, aux, counter);
+
+                for (pattern, body) in cases {
+                    let pattern_str = self.gen_ir_hvm(pattern, aux, counter);
+                    let body_str = self.gen_ir_hvm(body, aux, counter);
+                    aux.push(format!("({} {}) = {}", match_fn, pattern_str, body_str));
+                }
+                let default_str = self.gen_ir_hvm(default, aux, counter);
+                aux.push(format!("({} _) = {}", match_fn, default_str));
+
+                format!("({} {})", match_fn, cond_str)
+            }
+
+            IRKind::Unary { op, right } => format!("({} {})", hvm_op(op), self.gen_ir_hvm(right, aux, counter)),
+
+            IRKind::Binary { left, op, right } => {
+                format!(
+                    "({} {} {})",
+                    hvm_op(op),
+                    self.gen_ir_hvm(left, aux, counter),
+                    self.gen_ir_hvm(right, aux, counter),
+                )
+            }
+
+            IRKind::Value { value } => match value {
+                // HVM numbers are unsigned 60-bit machine words.
+                Value::Int(value) => format!("{}", value),
+                Value::Boolean(value) => if *value { "#T".to_string() } else { "#F".to_string() },
+                Value::String(value) => format!("\"{}\"", value),
+                Value::Ident(value) => format!("v_{}", value),
+            },
+
+            IRKind::Vector { values } => {
+                values.iter().rev().fold("Nil".to_string(), |tail, value| {
+                    format!("(Cons {} {})", self.gen_ir_hvm(value, aux, counter), tail)
+                })
+            }
+
+            #[allow(unreachable_patterns)]
+            _ => { dbg!(ir); todo!() },
+        }
+    }
+}
+
+fn hvm_op(op: &str) -> &str {
+    match op {
+        "+" => "+",
+        "-" => "-",
+        "*" => "*",
+        "/" => "/",
+        "<" => "<",
+        ">" => ">",
+        "==" => "==",
+        "!=" => "!=",
+        other => other,
+    }
+}
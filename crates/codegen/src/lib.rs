@@ -0,0 +1,16 @@
+mod hvm;
+pub mod optimize;
+mod sourcemap;
+mod ts;
+
+pub use optimize::optimize;
+pub use ts::Codegen;
+
+/// Which backend `Codegen::gen` lowers a `Vec<IR>` to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// TypeScript, run through Deno.
+    Ts,
+    /// HVM (Higher-order Virtual Machine) rewrite rules, run in parallel.
+    Hvm,
+}
@@ -0,0 +1,218 @@
+use hir::{IRKind, Value, IR};
+
+/// Fold constants and collapse algebraic identities in every `IR` before
+/// it reaches `Codegen::gen`, so e.g. `arg + 0 - arg * 1 + 1 + 2 + 3`
+/// collapses to a minimal form instead of being emitted verbatim.
+pub fn optimize(irs: Vec<IR>) -> Vec<IR> {
+    irs.into_iter().map(|ir| IR { kind: fold(ir.kind), span: ir.span }).collect()
+}
+
+fn fold_box(kind: Box<IRKind>) -> Box<IRKind> {
+    Box::new(fold(*kind))
+}
+
+fn fold_int(op: &str, lhs: i64, rhs: i64) -> Option<IRKind> {
+    let value = match op {
+        "+" => lhs + rhs,
+        "-" => lhs - rhs,
+        "*" => lhs * rhs,
+        "/" if rhs != 0 => lhs / rhs,
+        _ => return None,
+    };
+    Some(IRKind::Value { value: Value::Int(value) })
+}
+
+fn as_int(kind: &IRKind) -> Option<i64> {
+    match kind {
+        IRKind::Value { value: Value::Int(i) } => Some(*i),
+        _ => None,
+    }
+}
+
+/// Whether folding `kind` away would silently drop a `Call`/`Intrinsic`
+/// that still needs to run for its side effect.
+fn has_side_effect(kind: &IRKind) -> bool {
+    match kind {
+        IRKind::Call { .. } | IRKind::Intrinsic { .. } => true,
+        IRKind::Unary { right, .. } => has_side_effect(right),
+        IRKind::Binary { left, right, .. } => has_side_effect(left) || has_side_effect(right),
+        IRKind::Vector { values } => values.iter().any(has_side_effect),
+        _ => false,
+    }
+}
+
+/// Recursively fold `kind` bottom-up: children are folded first, then the
+/// node itself is simplified. Side-effecting `Call`/`Intrinsic` subtrees are
+/// always kept, even when their value ends up unused.
+fn fold(kind: IRKind) -> IRKind {
+    match kind {
+        IRKind::Define { public, name, type_hint, value, mutable } => IRKind::Define {
+            public,
+            name,
+            type_hint,
+            value: fold_box(value),
+            mutable,
+        },
+
+        IRKind::Call { name, args } => IRKind::Call {
+            name,
+            args: args.into_iter().map(fold).collect(),
+        },
+
+        IRKind::Intrinsic { name, args } => IRKind::Intrinsic {
+            name,
+            args: args.into_iter().map(fold).collect(),
+        },
+
+        IRKind::Fun { public, name, return_type_hint, args, body } => IRKind::Fun {
+            public,
+            name,
+            return_type_hint,
+            args,
+            body: fold_box(body),
+        },
+
+        IRKind::Return { value } => IRKind::Return { value: fold_box(value) },
+
+        IRKind::Do { body } => {
+            IRKind::Do { body: body.into_iter().map(|ir| IR { kind: fold(ir.kind), span: ir.span }).collect() }
+        }
+
+        IRKind::If { cond, body, else_body } => IRKind::If {
+            cond: fold_box(cond),
+            body: fold_box(body),
+            else_body: fold_box(else_body),
+        },
+
+        IRKind::Case { cond, cases, default } => IRKind::Case {
+            cond: fold_box(cond),
+            cases: cases.into_iter().map(|(pattern, body)| (fold(pattern), fold(body))).collect(),
+            default: fold_box(default),
+        },
+
+        IRKind::Unary { op, right } => {
+            let right = fold(*right);
+            match (op.as_str(), as_int(&right)) {
+                ("+", Some(i)) => IRKind::Value { value: Value::Int(i) },
+                ("-", Some(i)) => IRKind::Value { value: Value::Int(-i) },
+                _ => IRKind::Unary { op, right: Box::new(right) },
+            }
+        }
+
+        IRKind::Binary { left, op, right } => {
+            let left = fold(*left);
+            let right = fold(*right);
+
+            if let (Some(lhs), Some(rhs)) = (as_int(&left), as_int(&right)) {
+                if let Some(folded) = fold_int(&op, lhs, rhs) {
+                    return folded;
+                }
+            }
+
+            // Algebraic identities, only once both sides are already folded.
+            match (op.as_str(), &left, &right) {
+                ("+", IRKind::Value { value: Value::Int(0) }, _) => return right,
+                ("+", _, IRKind::Value { value: Value::Int(0) }) => return left,
+                ("*", IRKind::Value { value: Value::Int(1) }, _) => return right,
+                ("*", _, IRKind::Value { value: Value::Int(1) }) => return left,
+                ("*", IRKind::Value { value: Value::Int(0) }, _) if !has_side_effect(&right) => {
+                    return IRKind::Value { value: Value::Int(0) }
+                }
+                ("*", _, IRKind::Value { value: Value::Int(0) }) if !has_side_effect(&left) => {
+                    return IRKind::Value { value: Value::Int(0) }
+                }
+                ("-", _, IRKind::Value { value: Value::Int(0) }) => return left,
+                _ => {}
+            }
+
+            if op == "-" && matches!((&left, &right), (IRKind::Value { value: Value::Ident(a) }, IRKind::Value { value: Value::Ident(b) }) if a == b) {
+                return IRKind::Value { value: Value::Int(0) };
+            }
+
+            IRKind::Binary { left: Box::new(left), op, right: Box::new(right) }
+        }
+
+        IRKind::Value { value } => IRKind::Value { value },
+
+        IRKind::Vector { values } => IRKind::Vector { values: values.into_iter().map(fold).collect() },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Codegen;
+
+    fn int(i: i64) -> IRKind {
+        IRKind::Value { value: Value::Int(i) }
+    }
+
+    fn ident(name: &str) -> IRKind {
+        IRKind::Value { value: Value::Ident(name.to_string()) }
+    }
+
+    fn binary(left: IRKind, op: &str, right: IRKind) -> IRKind {
+        IRKind::Binary { left: Box::new(left), op: op.to_string(), right: Box::new(right) }
+    }
+
+    fn stmt(kind: IRKind) -> IR {
+        IR { kind, span: 0..0 }
+    }
+
+    fn gen(kind: IRKind) -> String {
+        let folded = fold(kind);
+        Codegen::new(crate::Target::Ts).gen_ir(&folded, false).0
+    }
+
+    #[test]
+    fn folds_constant_arithmetic() {
+        assert_eq!(gen(binary(int(1), "+", int(2))), "3");
+    }
+
+    #[test]
+    fn collapses_identities() {
+        // arg + 0 - arg * 1 + 1 + 2 + 3 == 6
+        let expr = binary(
+            binary(
+                binary(binary(ident("arg"), "+", int(0)), "-", binary(ident("arg"), "*", int(1))),
+                "+",
+                int(1),
+            ),
+            "+",
+            binary(int(2), "+", int(3)),
+        );
+        assert_eq!(gen(expr), "6");
+    }
+
+    #[test]
+    fn leaves_divide_by_zero_untouched() {
+        let expr = binary(int(1), "/", int(0));
+        assert_eq!(gen(expr), "1 / 0");
+    }
+
+    #[test]
+    fn keeps_side_effecting_calls_even_when_unused() {
+        let call = IRKind::Call { name: "f".to_string(), args: vec![int(1)] };
+        let kind = IRKind::Do { body: vec![stmt(call), stmt(int(0))] };
+        match fold(kind) {
+            IRKind::Do { body } => {
+                assert!(matches!(&body[0].kind, IRKind::Call { .. }));
+            }
+            _ => panic!("expected Do"),
+        }
+    }
+
+    #[test]
+    fn folds_unary_with_a_constant_operand() {
+        assert_eq!(gen(IRKind::Unary { op: "-".to_string(), right: Box::new(int(5)) }), "-5");
+    }
+
+    #[test]
+    fn keeps_call_when_multiplied_by_zero() {
+        let call = IRKind::Call { name: "f".to_string(), args: vec![] };
+        match fold(binary(call, "*", int(0))) {
+            IRKind::Binary { left, .. } => assert!(matches!(*left, IRKind::Call { .. })),
+            other => panic!("expected the call to survive, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+const BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Resolve a byte offset into `source` to a 0-indexed `(line, column)` pair,
+/// the convention Source Map v3 mappings use.
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let mut line = 0;
+    let mut col = 0;
+    for c in source[..offset].chars() {
+        if c == '\n' {
+            line += 1;
+            col = 0;
+        } else {
+            col += 1;
+        }
+    }
+    (line, col)
+}
+
+fn vlq_encode(value: i64) -> String {
+    let mut value = if value < 0 { ((-value as u64) << 1) | 1 } else { (value as u64) << 1 };
+    let mut out = String::new();
+    loop {
+        let mut digit = (value & 0b11111) as u8;
+        value >>= 5;
+        if value > 0 {
+            digit |= 0b100000;
+        }
+        out.push(BASE64_CHARS[digit as usize] as char);
+        if value == 0 {
+            break;
+        }
+    }
+    out
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// One recorded mapping: a generated `(line, column)` position and the
+/// original source offset it was emitted from.
+struct Segment {
+    generated_line: usize,
+    generated_column: usize,
+    source_offset: usize,
+}
+
+/// Accumulates `(generated position -> source span)` segments while
+/// `Codegen` emits TypeScript, then renders them as a Source Map v3 payload.
+pub(crate) struct SourceMapBuilder {
+    filename: String,
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl SourceMapBuilder {
+    pub(crate) fn new(filename: String, source: String) -> Self {
+        Self { filename, source, segments: Vec::new() }
+    }
+
+    pub(crate) fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Record that the generated output currently at `(generated_line,
+    /// generated_column)` (0-indexed) corresponds to `source_offset`, a byte
+    /// offset into the original source.
+    pub(crate) fn record(&mut self, generated_line: usize, generated_column: usize, source_offset: usize) {
+        self.segments.push(Segment { generated_line, generated_column, source_offset });
+    }
+
+    /// Render the accumulated segments as a Source Map v3 JSON document.
+    pub(crate) fn to_json(&self) -> String {
+        format!(
+            "{{\"version\":3,\"sources\":[{}],\"sourcesContent\":[{}],\"names\":[],\"mappings\":\"{}\"}}",
+            json_string(&self.filename),
+            json_string(&self.source),
+            self.encode_mappings(),
+        )
+    }
+
+    fn encode_mappings(&self) -> String {
+        let max_line = self.segments.iter().map(|s| s.generated_line).max().unwrap_or(0);
+
+        let mut by_line: Vec<Vec<&Segment>> = (0..=max_line).map(|_| Vec::new()).collect();
+        for segment in &self.segments {
+            by_line[segment.generated_line].push(segment);
+        }
+
+        let mut prev_source_line = 0i64;
+        let mut prev_source_column = 0i64;
+        let mut out = String::new();
+
+        for (i, line) in by_line.iter_mut().enumerate() {
+            if i > 0 {
+                out.push(';');
+            }
+            line.sort_by_key(|s| s.generated_column);
+
+            let mut prev_generated_column = 0i64;
+            for (j, segment) in line.iter().enumerate() {
+                if j > 0 {
+                    out.push(',');
+                }
+                let (source_line, source_column) = line_col(&self.source, segment.source_offset);
+
+                out.push_str(&vlq_encode(segment.generated_column as i64 - prev_generated_column));
+                out.push_str(&vlq_encode(0)); // single source, sourceIndex never changes
+                out.push_str(&vlq_encode(source_line as i64 - prev_source_line));
+                out.push_str(&vlq_encode(source_column as i64 - prev_source_column));
+
+                prev_generated_column = segment.generated_column as i64;
+                prev_source_line = source_line as i64;
+                prev_source_column = source_column as i64;
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vlq_encodes_small_values() {
+        assert_eq!(vlq_encode(0), "A");
+        assert_eq!(vlq_encode(2), "E");
+        assert_eq!(vlq_encode(-1), "D");
+    }
+
+    #[test]
+    fn renders_sources_and_content() {
+        let builder = SourceMapBuilder::new("a.hz".to_string(), "1 + 2".to_string());
+        let json = builder.to_json();
+        assert!(json.contains("\"sources\":[\"a.hz\"]"));
+        assert!(json.contains("\"sourcesContent\":[\"1 + 2\"]"));
+        assert!(json.contains("\"mappings\":\"\""));
+    }
+
+    #[test]
+    fn encodes_a_single_segment_on_the_first_line() {
+        let mut builder = SourceMapBuilder::new("a.hz".to_string(), "1 + 2".to_string());
+        builder.record(0, 0, 0);
+        assert_eq!(builder.encode_mappings(), "AAAA");
+    }
+
+    #[test]
+    fn delta_encodes_across_generated_lines() {
+        // Second segment starts on generated line 1, column 2, mapping back
+        // to source offset 4 ('d', line 1 column 0 of "abc\ndef").
+        let mut builder = SourceMapBuilder::new("a.hz".to_string(), "abc\ndef".to_string());
+        builder.record(0, 0, 0);
+        builder.record(1, 2, 4);
+        assert_eq!(builder.encode_mappings(), "AAAA;EACA");
+    }
+
+    #[test]
+    fn sorts_segments_on_the_same_line_by_generated_column() {
+        let mut builder = SourceMapBuilder::new("a.hz".to_string(), "ab".to_string());
+        builder.record(0, 2, 1);
+        builder.record(0, 0, 0);
+        // Out of insertion order, but column 0 must be encoded first.
+        assert_eq!(builder.encode_mappings(), "AAAA,EAAC");
+    }
+}
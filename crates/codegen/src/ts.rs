@@ -2,71 +2,165 @@ use std::fmt::Display;
 
 use hir::{IR, IRKind, Value};
 
+use crate::sourcemap::SourceMapBuilder;
+use crate::Target;
+
+/// A `gen_ir` result's extra mappings: `(byte offset into the returned
+/// string, source offset that text was lowered from)`, one per statement of
+/// a `Do` body it contains. Offsets are always relative to the string
+/// `gen_ir` returned them alongside, and need shifting by however far that
+/// string is embedded in its caller's own output.
+type Marks = Vec<(usize, usize)>;
+
+fn shift(marks: Marks, by: usize) -> Marks {
+    marks.into_iter().map(|(offset, source_offset)| (offset + by, source_offset)).collect()
+}
+
 pub struct Codegen {
     pub emitted: String,
+    target: Target,
+    source_map: Option<SourceMapBuilder>,
 }
 
 impl Codegen {
-    pub fn new() -> Self {
-        Self { emitted: String::new() }
+    pub fn new(target: Target) -> Self {
+        Self { emitted: String::new(), target, source_map: None }
+    }
+
+    /// Like `new`, but also accumulates a Source Map v3 payload mapping the
+    /// emitted TypeScript back to `source`, retrievable via `source_map`
+    /// once `gen` has run.
+    pub fn with_source_map(target: Target, filename: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            emitted: String::new(),
+            target,
+            source_map: Some(SourceMapBuilder::new(filename.into(), source.into())),
+        }
     }
 
-    fn emit<T: Display>(&mut self, t: T) {
+    /// The Source Map v3 JSON for this run, if `with_source_map` was used to
+    /// construct it.
+    pub fn source_map(&self) -> Option<String> {
+        self.source_map.as_ref().map(SourceMapBuilder::to_json)
+    }
+
+    pub(crate) fn emit<T: Display>(&mut self, t: T) {
         self.emitted.push_str(&t.to_string());
     }
 
+    /// The `(line, column)`, 0-indexed, that the next byte appended to
+    /// `self.emitted` would land on.
+    fn generated_position(&self) -> (usize, usize) {
+        Self::position_after(&self.emitted, "", 0)
+    }
+
+    /// The `(line, column)`, 0-indexed, of byte offset `offset` into `text`,
+    /// given that `text` is about to be appended right after `prefix`.
+    fn position_after(prefix: &str, text: &str, offset: usize) -> (usize, usize) {
+        let mut line = prefix.matches('\n').count();
+        let mut column = prefix.rsplit('\n').next().unwrap_or("").chars().count();
+        for c in text[..offset].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 0;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+
     pub fn gen(&mut self, irs: Vec<IR>) {
+        match self.target {
+            Target::Ts => self.gen_ts(irs),
+            Target::Hvm => self.gen_hvm(irs),
+        }
+    }
+
+    /// One mapping is recorded per top-level `IR`, plus one more per
+    /// statement of each `Do` body reached only through `Fun` bodies and
+    /// other `Do` bodies — `IRKind` doesn't carry a span itself, but each
+    /// statement inside a `Do` is a full `IR` and does, so `gen_ir` threads
+    /// those back up as `Marks` alongside its normal string output. A `Do`
+    /// nested anywhere else (an `If`/`Case` branch, a `Define` or `Return`
+    /// value, a call argument) gets no mappings of its own and collapses
+    /// onto its enclosing statement.
+    fn gen_ts(&mut self, irs: Vec<IR>) {
         self.emit(format!("// Auto-generated by hazure compiler version {}\n", env!("CARGO_PKG_VERSION")));
         self.emit("import { read, write, readFile, writeFile } from \"https://raw.githubusercontent.com/azur1s/hazure/master/runtime/io.ts\"\n");
 
         for ir in irs {
-            self.emit(&self.gen_ir(&ir.kind, true));
+            let (line, column) = self.generated_position();
+            let (text, marks) = self.gen_ir(&ir.kind, true);
+
+            if let Some(map) = self.source_map.as_mut() {
+                map.record(line, column, ir.span.start);
+                for (offset, source_offset) in marks {
+                    let (line, column) = Self::position_after(&self.emitted, &text, offset);
+                    map.record(line, column, source_offset);
+                }
+            }
+
+            self.emit(&text);
         }
 
         self.emit("f_main();");
+
+        if let Some(map) = &self.source_map {
+            self.emit(format!("\n//# sourceMappingURL={}.map\n", map.filename()));
+        }
     }
 
-    fn gen_ir(&self, ir: &IRKind, should_gen_semicolon: bool) -> String {
+    /// Lower `ir` to TypeScript, returning the generated text alongside any
+    /// per-statement `Marks` gathered from `Do` bodies nested inside it.
+    /// Expression-position children (call args, operands, vector elements)
+    /// don't carry their own marks forward — only the statement-sequence
+    /// positions (`Do`, and `Fun`'s body) do.
+    pub(crate) fn gen_ir(&self, ir: &IRKind, should_gen_semicolon: bool) -> (String, Marks) {
         #[macro_export]
         macro_rules! semicolon { () => { if should_gen_semicolon { ";" } else { "" } }; }
 
         match ir {
             IRKind::Define { public, name, type_hint, value, mutable } => {
-                format!(
+                let (value_str, _) = self.gen_ir(value, false);
+                let text = format!(
                     "{} {} v_{}: {} = {}{}\n",
                     if *public { "export" } else { "" },
                     if *mutable { "let" } else { "const" },
-                    name, 
+                    name,
                     type_hint,
-                    self.gen_ir(value, false),
+                    value_str,
                     semicolon!()
-                )
+                );
+                (text, Marks::new())
             },
 
             IRKind::Call { name, args } => {
-                format!(
+                let text = format!(
                     "f_{}({}){}",
                     name,
                     args
                         .iter()
-                        .map(|arg| self.gen_ir(arg, false))
+                        .map(|arg| self.gen_ir(arg, false).0)
                         .collect::<Vec<_>>()
                         .join(", ")
                         .trim_end_matches(";\n"),
                     semicolon!(),
-                )
+                );
+                (text, Marks::new())
             },
 
             IRKind::Intrinsic { name, args } => {
-                match name.as_str() {
-                    "write"      => { format!("write({}){}\n"        , self.gen_ir(&args[0], false), semicolon!()) },
-                    "write_file" => { format!("writeFile({}, {}){}\n", self.gen_ir(&args[0], false), self.gen_ir(&args[1], false), semicolon!()) },
-                    "read"       => { format!("read({}){}\n"         , self.gen_ir(&args[0], false), semicolon!()) },
-                    "read_file"  => { format!("readFile({}){}\n"     , self.gen_ir(&args[0], false), semicolon!()) }
-                    "emit" => { format!("{}", self.gen_ir(&args[0], false).trim_start_matches('"').trim_end_matches('"')) },
-                    "get" => { format!("{}[{}]", self.gen_ir(&args[0], false), self.gen_ir(&args[1], false)) },
+                let text = match name.as_str() {
+                    "write"      => { format!("write({}){}\n"        , self.gen_ir(&args[0], false).0, semicolon!()) },
+                    "write_file" => { format!("writeFile({}, {}){}\n", self.gen_ir(&args[0], false).0, self.gen_ir(&args[1], false).0, semicolon!()) },
+                    "read"       => { format!("read({}){}\n"         , self.gen_ir(&args[0], false).0, semicolon!()) },
+                    "read_file"  => { format!("readFile({}){}\n"     , self.gen_ir(&args[0], false).0, semicolon!()) }
+                    "emit" => { format!("{}", self.gen_ir(&args[0], false).0.trim_start_matches('"').trim_end_matches('"')) },
+                    "get" => { format!("{}[{}]", self.gen_ir(&args[0], false).0, self.gen_ir(&args[1], false).0) },
                     _ => unreachable!(format!("Unknown intrinsic: {}", name)) // Shoul be handled by lowering
-                }
+                };
+                (text, Marks::new())
             },
 
             IRKind::Fun { public, name, return_type_hint, args, body } => {
@@ -75,86 +169,98 @@ impl Codegen {
                     .map(|arg| format!("v_{}: {}", arg.0, arg.1))
                     .collect::<Vec<_>>().
                     join(", ");
-                format!(
-                    "{} const f_{} = ({}): {} => {};\n",
+                let prefix = format!(
+                    "{} const f_{} = ({}): {} => ",
                     if *public { "export" } else { "" },
                     name,
                     args,
                     return_type_hint,
-                    self.gen_ir(body, false)
-                )
+                );
+                let (body_str, body_marks) = self.gen_ir(body, false);
+                let text = format!("{}{};\n", prefix, body_str);
+                (text, shift(body_marks, prefix.len()))
             },
 
             IRKind::Return { value } => {
-                format!(
-                    "return {};\n",
-                    self.gen_ir(value, false)
-                )
+                let (value_str, _) = self.gen_ir(value, false);
+                let text = format!("return {};\n", value_str);
+                (text, Marks::new())
             },
 
             IRKind::Do { body } => {
                 let mut out = "{\n".to_string();
-                for expr in body {
-                    out.push_str(&self.gen_ir(&expr, true));
+                let mut marks = Marks::new();
+                for stmt in body {
+                    let offset = out.len();
+                    marks.push((offset, stmt.span.start));
+
+                    let (stmt_str, stmt_marks) = self.gen_ir(&stmt.kind, true);
+                    marks.extend(shift(stmt_marks, offset));
+                    out.push_str(&stmt_str);
                 }
                 out.push_str("}\n");
-                out
+                (out, marks)
             },
 
             IRKind::If { cond, body, else_body } => {
-                format!(
-                    "if ({}) {{\n{}}} else {{\n{}}}\n",
-                    self.gen_ir(cond, true),
-                    self.gen_ir(body, true),
-                    self.gen_ir(else_body, true),
-                )
+                let (cond_str, _) = self.gen_ir(cond, true);
+                let (body_str, _) = self.gen_ir(body, true);
+                let (else_str, _) = self.gen_ir(else_body, true);
+                let text = format!("if ({}) {{\n{}}} else {{\n{}}}\n", cond_str, body_str, else_str);
+                (text, Marks::new())
             },
 
             IRKind::Case { cond, cases, default } => {
-                format!(
+                let (cond_str, _) = self.gen_ir(cond, true);
+                let text = format!(
                     "switch ({}) {{\n{}{}\n}}\n",
-                    self.gen_ir(cond, true),
+                    cond_str,
                     cases
                         .iter()
                         .map(|(pattern, body)| format!(
                             "case {}: {}\nbreak;\n",
-                            self.gen_ir(pattern, true),
-                            self.gen_ir(body, true)))
+                            self.gen_ir(pattern, true).0,
+                            self.gen_ir(body, true).0))
                         .collect::<Vec<_>>()
                         .join("\n"),
                     format!(
                         "default: {}\nbreak;\n",
-                        self.gen_ir(default, true),
+                        self.gen_ir(default, true).0,
                     ),
-                )
+                );
+                (text, Marks::new())
             },
 
             IRKind::Unary { op, right } => {
-                format!("{}{}", op, self.gen_ir(right, false))
+                let text = format!("{}{}", op, self.gen_ir(right, false).0);
+                (text, Marks::new())
             },
 
             IRKind::Binary { left, op, right } => {
-                format!("{} {} {}", self.gen_ir(left, false), op, self.gen_ir(right, false))
+                let text = format!("{} {} {}", self.gen_ir(left, false).0, op, self.gen_ir(right, false).0);
+                (text, Marks::new())
             },
 
             IRKind::Value { value } => {
-                match value {
+                let text = match value {
                     Value::Int(value)     => format!("{}", value),
                     Value::Boolean(value) => format!("{}", value),
                     Value::String(value)  => format!("\"{}\"", value),
                     Value::Ident(value)   => format!("v_{}", value),
-                }
+                };
+                (text, Marks::new())
             },
 
             IRKind::Vector { values } => {
-                format!(
+                let text = format!(
                     "[{}]",
                     values
                         .iter()
-                        .map(|value| self.gen_ir(value, false))
+                        .map(|value| self.gen_ir(value, false).0)
                         .collect::<Vec<_>>()
                         .join(", ")
-                )
+                );
+                (text, Marks::new())
             },
 
             #[allow(unreachable_patterns)]
@@ -0,0 +1,161 @@
+use std::ops::Range;
+
+use chumsky::error::{Simple, SimpleReason};
+use lexer::Token;
+
+/// Resolve a byte offset into `source` to a 1-indexed `(line, column)` pair.
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let mut line = 1;
+    let mut col = 1;
+    for c in source[..offset].chars() {
+        if c == '\n' {
+            line += 1;
+            col = 1;
+        } else {
+            col += 1;
+        }
+    }
+    (line, col)
+}
+
+/// The full text of the line containing `offset`, without its trailing newline.
+fn line_text(source: &str, offset: usize) -> &str {
+    let offset = offset.min(source.len());
+    let start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let end = source[offset..].find('\n').map(|i| offset + i).unwrap_or(source.len());
+    &source[start..end]
+}
+
+fn describe_reason(reason: &SimpleReason<Token, Range<usize>>) -> Option<String> {
+    match reason {
+        SimpleReason::Unclosed { span, delimiter } => {
+            Some(format!("unclosed delimiter {:?} opened at {:?}", delimiter, span))
+        }
+        SimpleReason::Unexpected => None,
+        SimpleReason::Custom(msg) => Some(msg.clone()),
+    }
+}
+
+/// Render one error's caret underline plus its `expected`/`found`/label/
+/// reason lines, against `line_text` (the already-resolved text of the
+/// line it starts on). The underline is clamped to what's left of that
+/// line, since a span can legitimately run past it (e.g. an unclosed
+/// delimiter whose span reaches the end of the file).
+fn render_annotation(line_text: &str, col: usize, error: &Simple<Token>) -> String {
+    let span = error.span();
+    let line_len = line_text.chars().count();
+    let underline_start = col.saturating_sub(1);
+    let max_len = line_len.saturating_sub(underline_start).max(1);
+    let underline_len = (span.end.saturating_sub(span.start)).max(1).min(max_len);
+
+    let expected = error
+        .expected()
+        .map(|tok| match tok {
+            Some(t) => format!("{:?}", t),
+            None => "end of input".to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    let found = match error.found() {
+        Some(t) => format!("{:?}", t),
+        None => "end of input".to_string(),
+    };
+
+    let mut out = format!(
+        "   | {}{} expected {}, found {}\n",
+        " ".repeat(underline_start),
+        "^".repeat(underline_len),
+        if expected.is_empty() { "something else".to_string() } else { expected.join(", ") },
+        found,
+    );
+
+    if let Some(label) = error.label() {
+        out.push_str(&format!("   | while parsing {}\n", label));
+    }
+
+    if let Some(reason) = describe_reason(error.reason()) {
+        out.push_str(&format!("   | {}\n", reason));
+    }
+
+    out
+}
+
+/// Render one line's worth of errors as a single framed block: the
+/// `-->` location header and the offending line printed once, followed by
+/// each error's caret underline and `expected`/`found` context.
+fn render_line_group(filename: &str, line: usize, text: &str, errors: &[&Simple<Token>], source: &str) -> String {
+    let (_, first_col) = line_col(source, errors[0].span().start);
+
+    let mut out = format!("error: unexpected token\n  --> {}:{}:{}\n", filename, line, first_col);
+    out.push_str(&format!("   |\n{:>3}| {}\n", line, text));
+
+    for error in errors {
+        let (_, col) = line_col(source, error.span().start);
+        out.push_str(&render_annotation(text, col, error));
+    }
+
+    out
+}
+
+/// Render every parser/lowering error against the original `source`. Errors
+/// that land on the same line share a single `-->`/source-line header, with
+/// one caret-underline annotation per error underneath it.
+///
+/// `filename` is only used for the `-->` location line; `source` must be the
+/// exact string the errors' byte-range spans were produced against.
+pub fn report_errors(filename: &str, source: &str, errors: &[Simple<Token>]) -> String {
+    let mut sorted: Vec<&Simple<Token>> = errors.iter().collect();
+    sorted.sort_by_key(|e| e.span().start);
+
+    let mut out = String::new();
+    let mut i = 0;
+    while i < sorted.len() {
+        let (line, _) = line_col(source, sorted[i].span().start);
+
+        let mut j = i + 1;
+        while j < sorted.len() && line_col(source, sorted[j].span().start).0 == line {
+            j += 1;
+        }
+        let group = &sorted[i..j];
+
+        let text = line_text(source, group[0].span().start);
+        out.push_str(&render_line_group(filename, line, text, group, source));
+        out.push('\n');
+
+        i = j;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chumsky::Error;
+
+    #[test]
+    fn groups_same_line_errors_under_one_header() {
+        let source = "1 + ;\nlet x = 2";
+        let errors = vec![
+            Simple::<Token>::expected_input_found(2..3, vec![], Some(Token::Identifier("+".to_string()))),
+            Simple::<Token>::expected_input_found(4..5, vec![], Some(Token::Identifier(";".to_string()))),
+        ];
+        let report = report_errors("a.hz", source, &errors);
+        assert_eq!(report.matches("-->").count(), 1);
+        assert_eq!(report.matches("1 + ;").count(), 1);
+        assert_eq!(report.matches('^').count(), 2);
+    }
+
+    #[test]
+    fn clamps_underline_to_the_printed_line() {
+        let source = "ab";
+        let errors = vec![Simple::<Token>::expected_input_found(
+            0..50,
+            vec![],
+            Some(Token::Identifier("x".to_string())),
+        )];
+        let report = report_errors("a.hz", source, &errors);
+        let caret_line = report.lines().find(|l| l.contains('^')).unwrap();
+        assert_eq!(caret_line.matches('^').count(), 2);
+    }
+}
@@ -32,6 +32,11 @@ pub enum Expr {
     Do {
         body: Vec<Spanned<Self>>
     },
+    Match {
+        cond: Box<Spanned<Self>>,
+        arms: Vec<(Spanned<Self>, Spanned<Self>)>,
+        default: Option<Box<Spanned<Self>>>,
+    },
 }
 
 fn expr_parser() -> impl Parser<Token, Vec<Spanned<Expr>>, Error = Simple<Token>> + Clone {
@@ -209,9 +214,38 @@ fn expr_parser() -> impl Parser<Token, Vec<Spanned<Expr>>, Error = Simple<Token>
                 )
             });
 
+        let match_ = just(Token::KwMatch)
+            .ignore_then(expr.clone())
+            .then(
+                expr.clone()
+                    .then_ignore(just(Token::FatArrow))
+                    .then(expr.clone())
+                    .then_ignore(just(Token::SemiColon))
+                    .repeated()
+            )
+            .then(
+                just(Token::KwElse)
+                    .ignore_then(just(Token::FatArrow))
+                    .ignore_then(expr.clone())
+                    .then_ignore(just(Token::SemiColon))
+                    .or_not()
+            )
+            .then_ignore(just(Token::KwEnd))
+            .map_with_span(|((cond, arms), default), span| {
+                (
+                    Expr::Match {
+                        cond: Box::new(cond),
+                        arms,
+                        default: default.map(Box::new),
+                    },
+                    span,
+                )
+            });
+
         let_
             .or(fun)
             .or(do_block)
+            .or(match_)
             .or(compare)
     }).labelled("expression");
 
@@ -228,4 +262,198 @@ pub fn parse(tokens: Vec<(Token, std::ops::Range<usize>)>, len: usize) -> (Optio
     ));
 
     return (ast, parse_error)
+}
+
+/// Compare two parsed programs for structural equality while ignoring every
+/// `Spanned` range, which shifts on every whitespace change and would
+/// otherwise make snapshot tests brittle.
+pub fn eq_ignore_span(a: &[Spanned<Expr>], b: &[Spanned<Expr>]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|((a, _), (b, _))| expr_eq(a, b))
+}
+
+fn spanned_eq(a: &Spanned<Expr>, b: &Spanned<Expr>) -> bool {
+    expr_eq(&a.0, &b.0)
+}
+
+fn spanned_str_eq(a: &Spanned<String>, b: &Spanned<String>) -> bool {
+    a.0 == b.0
+}
+
+fn args_eq(
+    a: &[(Spanned<String>, Spanned<String>)],
+    b: &[(Spanned<String>, Spanned<String>)],
+) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|((an, at), (bn, bt))| spanned_str_eq(an, bn) && spanned_str_eq(at, bt))
+}
+
+fn expr_eq(a: &Expr, b: &Expr) -> bool {
+    match (a, b) {
+        (Expr::Int(a), Expr::Int(b)) => a == b,
+        (Expr::Float(a), Expr::Float(b)) => a == b,
+        (Expr::Boolean(a), Expr::Boolean(b)) => a == b,
+        (Expr::String(a), Expr::String(b)) => a == b,
+        (Expr::Identifier(a), Expr::Identifier(b)) => a == b,
+
+        (Expr::Unary { op: a_op, rhs: a_rhs }, Expr::Unary { op: b_op, rhs: b_rhs }) => {
+            a_op == b_op && spanned_eq(a_rhs, b_rhs)
+        }
+
+        (
+            Expr::Binary { lhs: a_lhs, op: a_op, rhs: a_rhs },
+            Expr::Binary { lhs: b_lhs, op: b_op, rhs: b_rhs },
+        ) => a_op == b_op && spanned_eq(a_lhs, b_lhs) && spanned_eq(a_rhs, b_rhs),
+
+        (Expr::Call { name: a_name, args: a_args }, Expr::Call { name: b_name, args: b_args }) => {
+            spanned_eq(a_name, b_name) && eq_ignore_span(&a_args.0, &b_args.0)
+        }
+
+        (
+            Expr::Let { name: a_name, type_hint: a_hint, value: a_value },
+            Expr::Let { name: b_name, type_hint: b_hint, value: b_value },
+        ) => a_name == b_name && a_hint == b_hint && spanned_eq(a_value, b_value),
+
+        (
+            Expr::Fun { name: a_name, type_hint: a_hint, args: a_args, body: a_body },
+            Expr::Fun { name: b_name, type_hint: b_hint, args: b_args, body: b_body },
+        ) => {
+            a_name == b_name
+                && a_hint == b_hint
+                && args_eq(&a_args.0, &b_args.0)
+                && spanned_eq(a_body, b_body)
+        }
+
+        (
+            Expr::If { cond: a_cond, then: a_then, else_: a_else },
+            Expr::If { cond: b_cond, then: b_then, else_: b_else },
+        ) => spanned_eq(a_cond, b_cond) && spanned_eq(a_then, b_then) && spanned_eq(a_else, b_else),
+
+        (Expr::Do { body: a_body }, Expr::Do { body: b_body }) => eq_ignore_span(a_body, b_body),
+
+        (
+            Expr::Match { cond: a_cond, arms: a_arms, default: a_default },
+            Expr::Match { cond: b_cond, arms: b_arms, default: b_default },
+        ) => {
+            spanned_eq(a_cond, b_cond)
+                && a_arms.len() == b_arms.len()
+                && a_arms
+                    .iter()
+                    .zip(b_arms)
+                    .all(|((ap, ae), (bp, be))| spanned_eq(ap, bp) && spanned_eq(ae, be))
+                && match (a_default, b_default) {
+                    (Some(a), Some(b)) => spanned_eq(a, b),
+                    (None, None) => true,
+                    _ => false,
+                }
+        }
+
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spanned<T>(value: T) -> Spanned<T> {
+        (value, 0..0)
+    }
+
+    /// Parse `source` end-to-end through the lexer and `expr_parser`,
+    /// panicking with the parser's own errors on failure.
+    fn parse_source(source: &str) -> Vec<Spanned<Expr>> {
+        let tokens = lexer::lex(source);
+        let (ast, errors) = parse(tokens, source.len());
+        assert!(errors.is_empty(), "parse errors for `{}`: {:?}", source, errors);
+        ast.unwrap_or_else(|| panic!("no AST produced for `{}`", source))
+    }
+
+    /// One (source, expected AST) pair in the snapshot corpus. Spans in
+    /// `expected` are throwaway; only `eq_ignore_span` ever looks at them.
+    struct Case {
+        source: &'static str,
+        expected: fn() -> Vec<Spanned<Expr>>,
+    }
+
+    #[test]
+    fn snapshot_corpus() {
+        let cases = [
+            Case {
+                source: "let x: int = 1;",
+                expected: || {
+                    vec![spanned(Expr::Let {
+                        name: "x".to_string(),
+                        type_hint: "int".to_string(),
+                        value: Box::new(spanned(Expr::Int(1))),
+                    })]
+                },
+            },
+            Case {
+                source: "1 + 2 * 3;",
+                expected: || {
+                    vec![spanned(Expr::Binary {
+                        lhs: Box::new(spanned(Expr::Int(1))),
+                        op: "+".to_string(),
+                        rhs: Box::new(spanned(Expr::Binary {
+                            lhs: Box::new(spanned(Expr::Int(2))),
+                            op: "*".to_string(),
+                            rhs: Box::new(spanned(Expr::Int(3))),
+                        })),
+                    })]
+                },
+            },
+            Case {
+                source: "fun add(a: int) (b: int): int = a + b;",
+                expected: || {
+                    vec![spanned(Expr::Fun {
+                        name: "add".to_string(),
+                        type_hint: "int".to_string(),
+                        args: spanned(vec![
+                            (spanned("a".to_string()), spanned("int".to_string())),
+                            (spanned("b".to_string()), spanned("int".to_string())),
+                        ]),
+                        body: Box::new(spanned(Expr::Binary {
+                            lhs: Box::new(spanned(Expr::Identifier("a".to_string()))),
+                            op: "+".to_string(),
+                            rhs: Box::new(spanned(Expr::Identifier("b".to_string()))),
+                        })),
+                    })]
+                },
+            },
+            Case {
+                source: "match n 0 => 1; else => 2; end;",
+                expected: || {
+                    vec![spanned(Expr::Match {
+                        cond: Box::new(spanned(Expr::Identifier("n".to_string()))),
+                        arms: vec![(spanned(Expr::Int(0)), spanned(Expr::Int(1)))],
+                        default: Some(Box::new(spanned(Expr::Int(2)))),
+                    })]
+                },
+            },
+            Case {
+                source: "match n 0 => 1; end;",
+                expected: || {
+                    vec![spanned(Expr::Match {
+                        cond: Box::new(spanned(Expr::Identifier("n".to_string()))),
+                        arms: vec![(spanned(Expr::Int(0)), spanned(Expr::Int(1)))],
+                        default: None,
+                    })]
+                },
+            },
+        ];
+
+        for case in cases {
+            let actual = parse_source(case.source);
+            let expected = (case.expected)();
+            assert!(
+                eq_ignore_span(&actual, &expected),
+                "snapshot mismatch for `{}`\n  actual:   {:?}\n  expected: {:?}",
+                case.source,
+                actual,
+                expected,
+            );
+        }
+    }
 }
\ No newline at end of file